@@ -17,6 +17,23 @@ fn criterion_benchmark(c: &mut Criterion) {
             });
         })
     });
+
+    c.bench_function("Blowfish encrypt 1M (ECB interleaved)", |b| {
+        let bf = Blowfish::new(b"0123456789abcdef0123456789abcdef").unwrap();
+        let mut blocks = vec![[0_u8; 8]; 1024 * 1024 / 8];
+        b.iter(|| {
+            bf.encrypt_blocks(&mut blocks);
+        })
+    });
+
+    #[cfg(feature = "rayon")]
+    c.bench_function("Blowfish encrypt 1M (ECB parallel)", |b| {
+        let bf = Blowfish::new(b"0123456789abcdef0123456789abcdef").unwrap();
+        let mut blocks = vec![[0_u8; 8]; 1024 * 1024 / 8];
+        b.iter(|| {
+            bf.par_encrypt_blocks(&mut blocks);
+        })
+    });
 }
 
 criterion_group!(benches, criterion_benchmark);
@@ -0,0 +1,198 @@
+//! bcrypt / Eksblowfish expensive key setup built on the [`Blowfish`] core.
+//!
+//! This reuses the P-box/S-box state and the `encrypt_lr` feedback loop from
+//! the plain cipher, but drives them through the cost-parameterized, salted
+//! key expansion that bcrypt uses for password hashing, and emits the familiar
+//! `$2b$cost$saltHash` string.
+
+use crate::{Blowfish, BlowfishError};
+
+/// The constant enciphered to produce the bcrypt digest ("OrpheanBeholderScryDoubt").
+const MAGIC: [u8; 24] = *b"OrpheanBeholderScryDoubt";
+
+/// bcrypt's own base64 alphabet (note the leading `./` and the absence of padding).
+const ALPHABET: &[u8; 64] =
+    b"./ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789";
+
+/// Number of salt bytes bcrypt expects (128 bit).
+const SALT_LEN: usize = 16;
+/// Number of digest bytes emitted (the 24-byte ciphertext with its last byte dropped).
+const HASH_LEN: usize = 23;
+/// Maximum length of the null-terminated key accepted by the `2b` variant.
+const MAX_KEY_LEN: usize = 72;
+
+/// Compute the raw 23-byte Eksblowfish digest for the null-terminated `key`
+/// under `cost` and `salt`.
+fn eksblowfish(cost: u32, salt: &[u8; SALT_LEN], key: &[u8]) -> [u8; HASH_LEN] {
+    let mut bf = Blowfish::fresh();
+
+    bf.expand_key(salt, key);
+    for _ in 0..(1u64 << cost) {
+        bf.expand_key(&[], key);
+        bf.expand_key(&[], salt);
+    }
+
+    // Encrypt the 24-byte magic (three 8-byte ECB blocks) 64 times.
+    let mut ctext = MAGIC;
+    for _ in 0..64 {
+        ctext.chunks_exact_mut(8).for_each(|block| {
+            bf.encrypt_block(block.try_into().unwrap());
+        });
+    }
+
+    ctext[..HASH_LEN].try_into().unwrap()
+}
+
+/// Build the null-terminated, length-checked key buffer for the `2b` variant.
+fn make_key(password: &[u8]) -> Result<Vec<u8>, BlowfishError> {
+    // The `2b` variant keys on the password plus a trailing NUL, capped at 72 bytes.
+    if password.len() + 1 > MAX_KEY_LEN {
+        return Err(BlowfishError::Keysize);
+    }
+    let mut key = Vec::with_capacity(password.len() + 1);
+    key.extend_from_slice(password);
+    key.push(0);
+    Ok(key)
+}
+
+/// Hash `password` with the given `cost` (4..=31) and 16-byte `salt`, returning
+/// the `$2b$cost$saltHash` string.
+pub fn hash(password: &[u8], cost: u32, salt: &[u8]) -> Result<String, BlowfishError> {
+    if !(4..=31).contains(&cost) {
+        return Err(BlowfishError::Cost);
+    }
+    let salt: &[u8; SALT_LEN] = salt.try_into().map_err(|_| BlowfishError::SaltSize)?;
+
+    let key = make_key(password)?;
+    let digest = eksblowfish(cost, salt, &key);
+
+    Ok(format!(
+        "$2b${:02}${}{}",
+        cost,
+        encode_base64(salt),
+        encode_base64(&digest),
+    ))
+}
+
+/// Verify `password` against a `$2b$cost$saltHash` string in constant time with
+/// respect to the stored digest.
+pub fn verify(password: &[u8], hash: &str) -> Result<bool, BlowfishError> {
+    let (cost, salt, expected) = parse(hash)?;
+
+    let key = make_key(password)?;
+    let digest = eksblowfish(cost, &salt, &key);
+
+    Ok(constant_time_eq(&digest, &expected))
+}
+
+/// Parse a `$2b$cost$saltHash` string into its cost, raw salt and raw digest.
+fn parse(hash: &str) -> Result<(u32, [u8; SALT_LEN], [u8; HASH_LEN]), BlowfishError> {
+    let body = hash.strip_prefix("$2b$").ok_or(BlowfishError::InvalidHash)?;
+    let (cost, rest) = body.split_once('$').ok_or(BlowfishError::InvalidHash)?;
+
+    let cost: u32 = cost.parse().map_err(|_| BlowfishError::InvalidHash)?;
+    if !(4..=31).contains(&cost) {
+        return Err(BlowfishError::Cost);
+    }
+
+    // 16 salt bytes encode to 22 chars, 23 digest bytes to 31 chars.
+    if rest.len() != 22 + 31 {
+        return Err(BlowfishError::InvalidHash);
+    }
+    let (salt, digest) = rest.split_at(22);
+
+    let salt = decode_base64(salt, SALT_LEN)?.try_into().unwrap();
+    let digest = decode_base64(digest, HASH_LEN)?.try_into().unwrap();
+
+    Ok((cost, salt, digest))
+}
+
+/// Encode bytes using the bcrypt base64 alphabet (no padding).
+fn encode_base64(data: &[u8]) -> String {
+    let mut out = String::with_capacity(data.len().div_ceil(3) * 4);
+    for chunk in data.chunks(3) {
+        let c0 = chunk[0];
+        out.push(ALPHABET[(c0 >> 2) as usize] as char);
+        if chunk.len() == 1 {
+            out.push(ALPHABET[((c0 & 0x03) << 4) as usize] as char);
+            break;
+        }
+        let c1 = chunk[1];
+        out.push(ALPHABET[(((c0 & 0x03) << 4) | (c1 >> 4)) as usize] as char);
+        if chunk.len() == 2 {
+            out.push(ALPHABET[((c1 & 0x0f) << 2) as usize] as char);
+            break;
+        }
+        let c2 = chunk[2];
+        out.push(ALPHABET[(((c1 & 0x0f) << 2) | (c2 >> 6)) as usize] as char);
+        out.push(ALPHABET[(c2 & 0x3f) as usize] as char);
+    }
+    out
+}
+
+/// Decode a bcrypt base64 string into exactly `len` bytes.
+fn decode_base64(text: &str, len: usize) -> Result<Vec<u8>, BlowfishError> {
+    let value = |c: u8| ALPHABET.iter().position(|&a| a == c).map(|p| p as u8);
+
+    let mut out = Vec::with_capacity(len);
+    let mut chars = text.bytes();
+    while out.len() < len {
+        let c0 = chars.next().ok_or(BlowfishError::InvalidHash)?;
+        let c1 = chars.next().ok_or(BlowfishError::InvalidHash)?;
+        let v0 = value(c0).ok_or(BlowfishError::InvalidHash)?;
+        let v1 = value(c1).ok_or(BlowfishError::InvalidHash)?;
+        out.push((v0 << 2) | (v1 >> 4));
+        if out.len() == len {
+            break;
+        }
+
+        let c2 = chars.next().ok_or(BlowfishError::InvalidHash)?;
+        let v2 = value(c2).ok_or(BlowfishError::InvalidHash)?;
+        out.push((v1 << 4) | (v2 >> 2));
+        if out.len() == len {
+            break;
+        }
+
+        let c3 = chars.next().ok_or(BlowfishError::InvalidHash)?;
+        let v3 = value(c3).ok_or(BlowfishError::InvalidHash)?;
+        out.push((v2 << 6) | v3);
+    }
+    Ok(out)
+}
+
+/// Compare two byte slices without short-circuiting on the first mismatch.
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_verify_known_vector() {
+        // Short ASCII passwords hash identically under the 2a and 2b variants.
+        let hash = "$2b$05$CCCCCCCCCCCCCCCCCCCCC.E5YPO9kmyuRGyh0XouQYb4YMJKvyOeW";
+        assert!(verify(b"U*U", hash).unwrap());
+        assert!(!verify(b"U*V", hash).unwrap());
+    }
+
+    #[test]
+    fn test_roundtrip() {
+        let salt = [0x42u8; 16];
+        let hash = hash(b"correct horse battery staple", 6, &salt).unwrap();
+        assert!(hash.starts_with("$2b$06$"));
+        assert!(verify(b"correct horse battery staple", &hash).unwrap());
+        assert!(!verify(b"Tr0ub4dor&3", &hash).unwrap());
+    }
+
+    #[test]
+    fn test_base64_roundtrip() {
+        let data = [0x00, 0x11, 0x22, 0x33, 0x44, 0x55, 0x66, 0x77, 0x88, 0x99, 0xaa];
+        let encoded = encode_base64(&data);
+        assert_eq!(decode_base64(&encoded, data.len()).unwrap(), data);
+    }
+}
@@ -4,6 +4,14 @@ use std::{error::Error, fmt::Display, mem};
 
 mod blowfish_consts;
 
+pub mod bcrypt;
+pub mod modes;
+pub mod padding;
+pub mod stream;
+
+#[cfg(feature = "cipher")]
+mod rustcrypto;
+
 pub struct Blowfish {
     pbox: Vec<u32>,
     sbox: [Vec<u32>; 4],
@@ -15,7 +23,14 @@ impl Blowfish {
             return Err(BlowfishError::Keysize);
         }
 
-        let bf = Blowfish {
+        Ok(Blowfish::fresh().key_schedule(key))
+    }
+
+    /// Build the raw cipher state seeded from the pi constants, before any key
+    /// material has been mixed in. Shared by the plain key schedule and the
+    /// Eksblowfish setup in [`bcrypt`].
+    fn fresh() -> Self {
+        Blowfish {
             pbox: blowfish_consts::PBOX.to_vec(),
             sbox: [
                 blowfish_consts::SBOX0.to_vec(),
@@ -23,9 +38,7 @@ impl Blowfish {
                 blowfish_consts::SBOX2.to_vec(),
                 blowfish_consts::SBOX3.to_vec(),
             ],
-        };
-
-        Ok(bf.key_schedule(key))
+        }
     }
 
     #[inline(always)]
@@ -99,7 +112,126 @@ impl Blowfish {
         block[4..].copy_from_slice(&r.to_be_bytes());
     }
 
+    /// Encrypt many independent blocks in place (ECB).
+    ///
+    /// The blocks are processed four at a time with interleaved `(l, r)` state
+    /// so the compiler can overlap the dependent `round` additions across the
+    /// four lanes, hiding their latency. Because [`Blowfish`] is immutable after
+    /// key setup this takes `&self` and is safe to share across threads.
+    pub fn encrypt_blocks(&self, blocks: &mut [[u8; 8]]) {
+        let mut chunks = blocks.chunks_exact_mut(4);
+        for chunk in &mut chunks {
+            self.encrypt_x4(chunk.try_into().unwrap());
+        }
+        chunks
+            .into_remainder()
+            .iter_mut()
+            .for_each(|block| self.encrypt_block(block));
+    }
+
+    /// Decrypt many independent blocks in place (ECB); see [`encrypt_blocks`].
+    ///
+    /// [`encrypt_blocks`]: Blowfish::encrypt_blocks
+    pub fn decrypt_blocks(&self, blocks: &mut [[u8; 8]]) {
+        let mut chunks = blocks.chunks_exact_mut(4);
+        for chunk in &mut chunks {
+            self.decrypt_x4(chunk.try_into().unwrap());
+        }
+        chunks
+            .into_remainder()
+            .iter_mut()
+            .for_each(|block| self.decrypt_block(block));
+    }
+
+    /// Encrypt blocks in parallel across a rayon thread pool.
+    #[cfg(feature = "rayon")]
+    pub fn par_encrypt_blocks(&self, blocks: &mut [[u8; 8]]) {
+        use rayon::prelude::*;
+        blocks
+            .par_chunks_mut(4096)
+            .for_each(|chunk| self.encrypt_blocks(chunk));
+    }
+
+    /// Decrypt blocks in parallel across a rayon thread pool.
+    #[cfg(feature = "rayon")]
+    pub fn par_decrypt_blocks(&self, blocks: &mut [[u8; 8]]) {
+        use rayon::prelude::*;
+        blocks
+            .par_chunks_mut(4096)
+            .for_each(|chunk| self.decrypt_blocks(chunk));
+    }
+
+    #[inline(always)]
+    fn encrypt_x4(&self, blocks: &mut [[u8; 8]; 4]) {
+        let mut l = [0u32; 4];
+        let mut r = [0u32; 4];
+        for k in 0..4 {
+            l[k] = u32::from_be_bytes(blocks[k][..4].try_into().unwrap());
+            r[k] = u32::from_be_bytes(blocks[k][4..].try_into().unwrap());
+        }
+
+        self.pbox.array_chunks::<2>().take(8).for_each(|[pl, pr]| {
+            for k in 0..4 {
+                l[k] ^= pl;
+                r[k] ^= self.round(l[k]);
+                r[k] ^= pr;
+                l[k] ^= self.round(r[k]);
+            }
+        });
+
+        for k in 0..4 {
+            l[k] ^= self.pbox[16];
+            r[k] ^= self.pbox[17];
+            // The (l, r) swap from `encrypt_lr` is folded into the store order.
+            blocks[k][..4].copy_from_slice(&r[k].to_be_bytes());
+            blocks[k][4..].copy_from_slice(&l[k].to_be_bytes());
+        }
+    }
+
+    #[inline(always)]
+    fn decrypt_x4(&self, blocks: &mut [[u8; 8]; 4]) {
+        let mut l = [0u32; 4];
+        let mut r = [0u32; 4];
+        for k in 0..4 {
+            l[k] = u32::from_be_bytes(blocks[k][..4].try_into().unwrap());
+            r[k] = u32::from_be_bytes(blocks[k][4..].try_into().unwrap());
+        }
+
+        self.pbox
+            .array_chunks::<2>()
+            .rev()
+            .take(8)
+            .for_each(|[pr, pl]| {
+                for k in 0..4 {
+                    l[k] ^= pl;
+                    r[k] ^= self.round(l[k]);
+                    r[k] ^= pr;
+                    l[k] ^= self.round(r[k]);
+                }
+            });
+
+        for k in 0..4 {
+            l[k] ^= self.pbox[1];
+            r[k] ^= self.pbox[0];
+            blocks[k][..4].copy_from_slice(&r[k].to_be_bytes());
+            blocks[k][4..].copy_from_slice(&l[k].to_be_bytes());
+        }
+    }
+
     fn key_schedule(mut self, key: &[u8]) -> Self {
+        // The plain cipher setup is just the salted key expansion with a zero salt.
+        self.expand_key(&[], key);
+        self
+    }
+
+    /// The bcrypt `ExpandKey(salt, key)` step.
+    ///
+    /// XORs the cycling key into all 18 P entries, then runs the `encrypt_lr`
+    /// feedback loop over the P-box and every S-box. Before each block the
+    /// running `(l, r)` is XORed with successive 32-bit big endian words of the
+    /// salt, cycling through its halves. An empty salt contributes zero words,
+    /// which reduces this to the plain Blowfish key schedule.
+    pub(crate) fn expand_key(&mut self, salt: &[u8], key: &[u8]) {
         let mut rolling_key = std::iter::repeat(key).flatten().copied();
 
         self.pbox.iter_mut().for_each(|pb| {
@@ -110,10 +242,24 @@ impl Blowfish {
             *pb ^= subkey;
         });
 
+        let salt_words: Vec<u32> = salt
+            .chunks_exact(4)
+            .map(|c| u32::from_be_bytes(c.try_into().unwrap()))
+            .collect();
+        let mut si = 0;
+        let mut xor_salt = |l: &mut u32, r: &mut u32| {
+            if !salt_words.is_empty() {
+                *l ^= salt_words[si % salt_words.len()];
+                *r ^= salt_words[(si + 1) % salt_words.len()];
+                si += 2;
+            }
+        };
+
         let mut l = 0;
         let mut r = 0;
 
         for i in (0..18).step_by(2) {
+            xor_salt(&mut l, &mut r);
             self.encrypt_lr(&mut l, &mut r);
             self.pbox[i] = l;
             self.pbox[i + 1] = r;
@@ -121,19 +267,28 @@ impl Blowfish {
 
         for i in 0..4 {
             for j in (0..256).step_by(2) {
+                xor_salt(&mut l, &mut r);
                 self.encrypt_lr(&mut l, &mut r);
                 self.sbox[i][j] = l;
                 self.sbox[i][j + 1] = r;
             }
         }
-
-        self
     }
 }
 
 #[derive(Debug)]
 pub enum BlowfishError {
     Keysize,
+    /// The bcrypt cost parameter is outside the supported 4..=31 range.
+    Cost,
+    /// The bcrypt salt is not exactly 16 bytes.
+    SaltSize,
+    /// A `$2b$` hash string could not be parsed.
+    InvalidHash,
+    /// The PKCS#7 padding on a decrypted message was malformed.
+    Padding,
+    /// A block-mode buffer length was not a multiple of the 8-byte block size.
+    BlockAlignment,
 }
 
 impl Error for BlowfishError {}
@@ -142,6 +297,11 @@ impl Display for BlowfishError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             Self::Keysize => write!(f, "Invalid keysize"),
+            Self::Cost => write!(f, "Invalid bcrypt cost"),
+            Self::SaltSize => write!(f, "Invalid bcrypt salt size"),
+            Self::InvalidHash => write!(f, "Invalid bcrypt hash string"),
+            Self::Padding => write!(f, "Invalid PKCS#7 padding"),
+            Self::BlockAlignment => write!(f, "Buffer length is not a multiple of the block size"),
         }
     }
 }
@@ -193,4 +353,49 @@ mod test {
 
         assert_eq!(ciphertext, plaintext_orig);
     }
+
+    #[test]
+    fn test_bulk_matches_serial() {
+        let bf = Blowfish::new(&[
+            0x00, 0x11, 0x22, 0x33, 0x44, 0x55, 0x66, 0x77, 0x88, 0x99, 0xaa, 0xbb, 0xcc, 0xdd,
+            0xee, 0xff,
+        ])
+        .unwrap();
+
+        // 37 blocks exercises both the x4 fast path and the scalar remainder.
+        let plain: Vec<[u8; 8]> = (0..37u8).map(|i| [i; 8]).collect();
+
+        let mut serial = plain.clone();
+        serial.iter_mut().for_each(|b| bf.encrypt_block(b));
+
+        let mut bulk = plain.clone();
+        bf.encrypt_blocks(&mut bulk);
+        assert_eq!(bulk, serial);
+
+        bf.decrypt_blocks(&mut bulk);
+        assert_eq!(bulk, plain);
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn test_parallel_matches_serial() {
+        let bf = Blowfish::new(&[
+            0x00, 0x11, 0x22, 0x33, 0x44, 0x55, 0x66, 0x77, 0x88, 0x99, 0xaa, 0xbb, 0xcc, 0xdd,
+            0xee, 0xff,
+        ])
+        .unwrap();
+
+        // Enough blocks to span several rayon chunks.
+        let plain: Vec<[u8; 8]> = (0..10_000u32).map(|i| i.to_be_bytes().repeat(2).try_into().unwrap()).collect();
+
+        let mut serial = plain.clone();
+        serial.iter_mut().for_each(|b| bf.encrypt_block(b));
+
+        let mut parallel = plain.clone();
+        bf.par_encrypt_blocks(&mut parallel);
+        assert_eq!(parallel, serial);
+
+        bf.par_decrypt_blocks(&mut parallel);
+        assert_eq!(parallel, plain);
+    }
 }
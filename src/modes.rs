@@ -0,0 +1,309 @@
+//! Block-cipher modes of operation (CBC, CFB, OFB, CTR) layered over the
+//! single-block [`Blowfish`] primitives.
+//!
+//! A [`Cipher`] bundles the key-scheduled cipher with a mode and an 8-byte IV
+//! register. The register is advanced in place on every call, so a large input
+//! can be fed block by block across many [`Cipher::encrypt`]/[`Cipher::decrypt`]
+//! calls and still produce the same result as a single call over the whole
+//! buffer.
+
+use crate::{Blowfish, BlowfishError};
+
+/// A mode of operation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mode {
+    /// Electronic Codebook (each block encrypted independently; ignores the IV).
+    Ecb,
+    /// Cipher Block Chaining.
+    Cbc,
+    /// Cipher Feedback (full 64-bit feedback).
+    Cfb,
+    /// Output Feedback.
+    Ofb,
+    /// Counter mode (turns the cipher into a stream cipher; needs no padding).
+    Ctr,
+}
+
+/// A mode of operation bound to a cipher and an IV register.
+pub struct Cipher<'a> {
+    bf: &'a Blowfish,
+    mode: Mode,
+    iv: [u8; 8],
+    /// The current CTR keystream block and how many of its bytes have been
+    /// consumed, so a keystream can be resumed mid-block across calls.
+    ks: [u8; 8],
+    ks_pos: usize,
+}
+
+impl<'a> Cipher<'a> {
+    /// Create a new mode processor from a cipher, a mode and an 8-byte IV.
+    pub fn new(bf: &'a Blowfish, mode: Mode, iv: [u8; 8]) -> Self {
+        Cipher {
+            bf,
+            mode,
+            iv,
+            ks: [0; 8],
+            // No keystream buffered yet; the first CTR byte forces generation.
+            ks_pos: 8,
+        }
+    }
+
+    /// The current IV register.
+    ///
+    /// After processing, this holds the value to continue a streamed operation.
+    /// In CTR mode it is the next counter block; keystream bytes left over from
+    /// a partly consumed block are not part of it.
+    pub fn iv(&self) -> [u8; 8] {
+        self.iv
+    }
+
+    /// Encrypt `buf` in place, advancing the IV register.
+    ///
+    /// For the block modes (ECB, CBC, CFB, OFB) `buf.len()` must be a multiple
+    /// of 8, otherwise [`BlowfishError::BlockAlignment`] is returned and the
+    /// buffer is left untouched; CTR accepts any length.
+    pub fn encrypt(&mut self, buf: &mut [u8]) -> Result<(), BlowfishError> {
+        self.check_alignment(buf)?;
+        match self.mode {
+            Mode::Ecb => self.ecb_encrypt(buf),
+            Mode::Cbc => self.cbc_encrypt(buf),
+            Mode::Cfb => self.cfb_encrypt(buf),
+            Mode::Ofb => self.ofb(buf),
+            Mode::Ctr => self.ctr(buf),
+        }
+        Ok(())
+    }
+
+    /// Decrypt `buf` in place, advancing the IV register.
+    ///
+    /// For the block modes (ECB, CBC, CFB, OFB) `buf.len()` must be a multiple
+    /// of 8, otherwise [`BlowfishError::BlockAlignment`] is returned and the
+    /// buffer is left untouched; CTR accepts any length.
+    pub fn decrypt(&mut self, buf: &mut [u8]) -> Result<(), BlowfishError> {
+        self.check_alignment(buf)?;
+        match self.mode {
+            Mode::Ecb => self.ecb_decrypt(buf),
+            Mode::Cbc => self.cbc_decrypt(buf),
+            Mode::Cfb => self.cfb_decrypt(buf),
+            Mode::Ofb => self.ofb(buf),
+            Mode::Ctr => self.ctr(buf),
+        }
+        Ok(())
+    }
+
+    /// Reject non-block-aligned buffers for the block modes before any bytes
+    /// are processed, so partial trailing data can never leak through.
+    fn check_alignment(&self, buf: &[u8]) -> Result<(), BlowfishError> {
+        if self.mode != Mode::Ctr && !buf.len().is_multiple_of(8) {
+            return Err(BlowfishError::BlockAlignment);
+        }
+        Ok(())
+    }
+
+    /// Encrypt `data` of any length: append PKCS#7 padding, then run the mode.
+    ///
+    /// Intended for the block modes (ECB, CBC); the IV register is advanced as
+    /// usual.
+    pub fn encrypt_padded(&mut self, data: &[u8]) -> Vec<u8> {
+        let mut buf = crate::padding::pkcs7_pad(data);
+        // Padding guarantees a block-aligned buffer, so this cannot fail.
+        self.encrypt(&mut buf).expect("padded buffer is block aligned");
+        buf
+    }
+
+    /// Decrypt a padded ciphertext and strip its PKCS#7 padding.
+    ///
+    /// Returns [`BlowfishError::Padding`] if the ciphertext is empty or its
+    /// length is not a multiple of 8, or if the trailing padding is malformed.
+    pub fn decrypt_padded(&mut self, data: &[u8]) -> Result<Vec<u8>, crate::BlowfishError> {
+        if data.is_empty() || !data.len().is_multiple_of(8) {
+            return Err(crate::BlowfishError::Padding);
+        }
+        let mut buf = data.to_vec();
+        self.decrypt(&mut buf)?;
+        let unpadded = crate::padding::pkcs7_unpad(&buf)?.len();
+        buf.truncate(unpadded);
+        Ok(buf)
+    }
+
+    fn ecb_encrypt(&self, buf: &mut [u8]) {
+        for block in buf.chunks_exact_mut(8) {
+            self.bf.encrypt_block(block.try_into().unwrap());
+        }
+    }
+
+    fn ecb_decrypt(&self, buf: &mut [u8]) {
+        for block in buf.chunks_exact_mut(8) {
+            self.bf.decrypt_block(block.try_into().unwrap());
+        }
+    }
+
+    fn cbc_encrypt(&mut self, buf: &mut [u8]) {
+        for block in buf.chunks_exact_mut(8) {
+            xor(block, &self.iv);
+            self.bf.encrypt_block(block.try_into().unwrap());
+            self.iv.copy_from_slice(block);
+        }
+    }
+
+    fn cbc_decrypt(&mut self, buf: &mut [u8]) {
+        for block in buf.chunks_exact_mut(8) {
+            let ct: [u8; 8] = block.try_into().unwrap();
+            self.bf.decrypt_block(block.try_into().unwrap());
+            xor(block, &self.iv);
+            self.iv = ct;
+        }
+    }
+
+    fn cfb_encrypt(&mut self, buf: &mut [u8]) {
+        for block in buf.chunks_exact_mut(8) {
+            self.bf.encrypt_block(&mut self.iv);
+            xor(block, &self.iv);
+            // The ciphertext feeds back into the register.
+            self.iv.copy_from_slice(block);
+        }
+    }
+
+    fn cfb_decrypt(&mut self, buf: &mut [u8]) {
+        for block in buf.chunks_exact_mut(8) {
+            let ct: [u8; 8] = block.try_into().unwrap();
+            self.bf.encrypt_block(&mut self.iv);
+            xor(block, &self.iv);
+            self.iv = ct;
+        }
+    }
+
+    fn ofb(&mut self, buf: &mut [u8]) {
+        for block in buf.chunks_exact_mut(8) {
+            // The encrypted register feeds back before being XORed in.
+            self.bf.encrypt_block(&mut self.iv);
+            xor(block, &self.iv);
+        }
+    }
+
+    fn ctr(&mut self, buf: &mut [u8]) {
+        for b in buf {
+            if self.ks_pos == 8 {
+                // Encrypt the current counter to get a fresh keystream block,
+                // then advance the counter for the next one.
+                self.ks = self.iv;
+                self.bf.encrypt_block(&mut self.ks);
+                self.iv = u64::from_be_bytes(self.iv).wrapping_add(1).to_be_bytes();
+                self.ks_pos = 0;
+            }
+            *b ^= self.ks[self.ks_pos];
+            self.ks_pos += 1;
+        }
+    }
+}
+
+/// XOR `src` into `dst` byte-wise over the shorter of the two lengths.
+fn xor(dst: &mut [u8], src: &[u8]) {
+    dst.iter_mut().zip(src).for_each(|(d, s)| *d ^= s);
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::Blowfish;
+
+    const KEY: [u8; 16] = [
+        0x00, 0x11, 0x22, 0x33, 0x44, 0x55, 0x66, 0x77, 0x88, 0x99, 0xaa, 0xbb, 0xcc, 0xdd, 0xee,
+        0xff,
+    ];
+
+    #[test]
+    fn test_cbc_known_vector() {
+        // With a zero IV a single CBC block reduces to the plain block cipher,
+        // so this matches the single-block known-answer test in the crate root.
+        let bf = Blowfish::new(&KEY).unwrap();
+        let mut buf = [0x65, 0x18, 0xa1, 0xf5, 0xc8, 0xd9, 0xb6, 0x3c];
+        Cipher::new(&bf, Mode::Cbc, [0; 8]).encrypt(&mut buf).unwrap();
+        assert_eq!(buf, [0xda, 0xc6, 0x36, 0x86, 0x1d, 0x70, 0xbd, 0x8a]);
+    }
+
+    #[test]
+    fn test_cbc_reference_vector() {
+        // The canonical Eric Young Blowfish-CBC test vector.
+        let key = [
+            0x01, 0x23, 0x45, 0x67, 0x89, 0xAB, 0xCD, 0xEF, 0xF0, 0xE1, 0xD2, 0xC3, 0xB4, 0xA5,
+            0x96, 0x87,
+        ];
+        let iv = [0xFE, 0xDC, 0xBA, 0x98, 0x76, 0x54, 0x32, 0x10];
+        let mut buf = [
+            0x37, 0x36, 0x35, 0x34, 0x33, 0x32, 0x31, 0x20, 0x4E, 0x6F, 0x77, 0x20, 0x69, 0x73,
+            0x20, 0x74, 0x68, 0x65, 0x20, 0x74, 0x69, 0x6D, 0x65, 0x20, 0x66, 0x6F, 0x72, 0x20,
+            0x00, 0x00, 0x00, 0x00,
+        ];
+        let bf = Blowfish::new(&key).unwrap();
+        Cipher::new(&bf, Mode::Cbc, iv).encrypt(&mut buf).unwrap();
+        assert_eq!(
+            buf,
+            [
+                0x6B, 0x77, 0xB4, 0xD6, 0x30, 0x06, 0xDE, 0xE6, 0x05, 0xB1, 0x56, 0xE2, 0x74, 0x03,
+                0x97, 0x93, 0x58, 0xDE, 0xB9, 0xE7, 0x15, 0x46, 0x16, 0xD9, 0x59, 0xF1, 0x65, 0x2B,
+                0xD5, 0xFF, 0x92, 0xCC,
+            ]
+        );
+    }
+
+    fn roundtrip(mode: Mode, len: usize) {
+        let bf = Blowfish::new(&KEY).unwrap();
+        let iv = [0xde, 0xad, 0xbe, 0xef, 0x01, 0x23, 0x45, 0x67];
+        let plain: Vec<u8> = (0..len).map(|i| i as u8).collect();
+
+        let mut buf = plain.clone();
+        Cipher::new(&bf, mode, iv).encrypt(&mut buf).unwrap();
+        assert_ne!(buf, plain);
+        Cipher::new(&bf, mode, iv).decrypt(&mut buf).unwrap();
+        assert_eq!(buf, plain);
+    }
+
+    #[test]
+    fn test_roundtrips() {
+        roundtrip(Mode::Cbc, 64);
+        roundtrip(Mode::Cfb, 64);
+        roundtrip(Mode::Ofb, 64);
+        roundtrip(Mode::Ctr, 70); // not block-aligned
+    }
+
+    #[test]
+    fn test_ctr_streaming_matches_oneshot() {
+        let bf = Blowfish::new(&KEY).unwrap();
+        let iv = [1, 2, 3, 4, 5, 6, 7, 8];
+        let plain: Vec<u8> = (0..40u8).collect();
+
+        let mut oneshot = plain.clone();
+        Cipher::new(&bf, Mode::Ctr, iv).encrypt(&mut oneshot).unwrap();
+
+        // Split on a non-block boundary to exercise mid-block keystream resume.
+        let mut streamed = plain.clone();
+        let mut c = Cipher::new(&bf, Mode::Ctr, iv);
+        for chunk in streamed.chunks_mut(3) {
+            c.encrypt(chunk).unwrap();
+        }
+        assert_eq!(streamed, oneshot);
+    }
+
+    #[test]
+    fn test_block_modes_reject_misaligned() {
+        let bf = Blowfish::new(&KEY).unwrap();
+        let iv = [0; 8];
+
+        // A 9-byte buffer must be rejected outright rather than leaking the
+        // trailing byte through as plaintext.
+        let mut buf = b"hello wor".to_vec();
+        let before = buf.clone();
+        let err = Cipher::new(&bf, Mode::Cbc, iv).encrypt(&mut buf);
+        assert!(matches!(err, Err(BlowfishError::BlockAlignment)));
+        assert_eq!(buf, before);
+
+        assert!(matches!(
+            Cipher::new(&bf, Mode::Ecb, iv).decrypt(&mut buf),
+            Err(BlowfishError::BlockAlignment)
+        ));
+
+        // CTR is a stream cipher and accepts any length.
+        assert!(Cipher::new(&bf, Mode::Ctr, iv).encrypt(&mut buf).is_ok());
+    }
+}
@@ -0,0 +1,91 @@
+//! PKCS#7 padding for the Blowfish 8-byte block size.
+
+use crate::BlowfishError;
+
+/// The Blowfish block size in bytes.
+const BLOCK_SIZE: usize = 8;
+
+/// Append PKCS#7 padding so the length becomes a multiple of 8.
+///
+/// If the length is already a multiple of 8 a full block of `0x08` is appended,
+/// so the padding can always be recognised and removed unambiguously.
+pub fn pkcs7_pad(data: &[u8]) -> Vec<u8> {
+    let n = BLOCK_SIZE - data.len() % BLOCK_SIZE;
+    let mut out = Vec::with_capacity(data.len() + n);
+    out.extend_from_slice(data);
+    out.resize(data.len() + n, n as u8);
+    out
+}
+
+/// Validate and strip PKCS#7 padding, returning the message slice.
+///
+/// The trailing byte count is checked in constant time with respect to the
+/// padding bytes. Returns [`BlowfishError::Padding`] if the last byte is outside
+/// `1..=8`, the message is shorter than the claimed padding, or any of the
+/// padding bytes disagree.
+pub fn pkcs7_unpad(data: &[u8]) -> Result<&[u8], BlowfishError> {
+    let n = *data.last().ok_or(BlowfishError::Padding)? as usize;
+    if !(1..=BLOCK_SIZE).contains(&n) || n > data.len() {
+        return Err(BlowfishError::Padding);
+    }
+
+    // Verify every padding byte equals `n` without short-circuiting.
+    let bad = data[data.len() - n..]
+        .iter()
+        .fold(0u8, |acc, &b| acc | (b ^ n as u8));
+    if bad != 0 {
+        return Err(BlowfishError::Padding);
+    }
+
+    Ok(&data[..data.len() - n])
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::modes::{Cipher, Mode};
+    use crate::Blowfish;
+
+    #[test]
+    fn test_pad_lengths() {
+        assert_eq!(pkcs7_pad(b"1234567").len(), 8);
+        assert_eq!(pkcs7_pad(b"1234567"), b"1234567\x01");
+        // A full block is added when already aligned.
+        assert_eq!(pkcs7_pad(b"12345678").len(), 16);
+        assert_eq!(&pkcs7_pad(b"12345678")[8..], &[8; 8]);
+    }
+
+    #[test]
+    fn test_unpad_roundtrip() {
+        for len in 0..20 {
+            let msg: Vec<u8> = (0..len as u8).collect();
+            let padded = pkcs7_pad(&msg);
+            assert_eq!(padded.len() % 8, 0);
+            assert_eq!(pkcs7_unpad(&padded).unwrap(), &msg[..]);
+        }
+    }
+
+    #[test]
+    fn test_unpad_rejects_corruption() {
+        assert!(matches!(pkcs7_unpad(&[1, 2, 3, 0]), Err(BlowfishError::Padding)));
+        assert!(matches!(pkcs7_unpad(&[1, 2, 3, 9]), Err(BlowfishError::Padding)));
+        // Claims 3 bytes of padding but they do not all match.
+        assert!(matches!(pkcs7_unpad(&[0, 3, 2, 3]), Err(BlowfishError::Padding)));
+    }
+
+    #[test]
+    fn test_message_roundtrip_cbc() {
+        let bf = Blowfish::new(&[
+            0x00, 0x11, 0x22, 0x33, 0x44, 0x55, 0x66, 0x77, 0x88, 0x99, 0xaa, 0xbb, 0xcc, 0xdd,
+            0xee, 0xff,
+        ])
+        .unwrap();
+        let iv = [0xde, 0xad, 0xbe, 0xef, 0x01, 0x23, 0x45, 0x67];
+        let msg = b"the quick brown fox";
+
+        let ct = Cipher::new(&bf, Mode::Cbc, iv).encrypt_padded(msg);
+        assert_eq!(ct.len() % 8, 0);
+        let pt = Cipher::new(&bf, Mode::Cbc, iv).decrypt_padded(&ct).unwrap();
+        assert_eq!(pt, msg);
+    }
+}
@@ -0,0 +1,116 @@
+//! RustCrypto [`cipher`] trait implementations for [`Blowfish`].
+//!
+//! These wrap the inherent `encrypt_block`/`decrypt_block` primitives into the
+//! generic `Block<Self>` interface, letting the cipher be paired with the
+//! ready-made `cbc`, `ctr` and `cfb` mode crates. The dependency is gated behind
+//! the `cipher` feature so the core crate stays dependency-free.
+//!
+//! `KeyInit` cannot fail, so its key-size check is fixed at the accepted
+//! 4..=56 byte range via `new_from_slice`; the inherent [`Blowfish::new`] keeps
+//! returning [`BlowfishError::Keysize`] for out-of-range keys.
+
+use cipher::{
+    consts::{U1, U8},
+    inout::InOut,
+    Block, BlockCipherDecBackend, BlockCipherDecClosure, BlockCipherDecrypt, BlockCipherEncBackend,
+    BlockCipherEncClosure, BlockCipherEncrypt, BlockSizeUser, InvalidLength, Key, KeyInit,
+    KeySizeUser, ParBlocksSizeUser,
+};
+
+use crate::Blowfish;
+
+impl BlockSizeUser for Blowfish {
+    type BlockSize = U8;
+}
+
+impl KeySizeUser for Blowfish {
+    // The largest accepted key; shorter keys are allowed via `new_from_slice`.
+    type KeySize = cipher::consts::U56;
+}
+
+impl KeyInit for Blowfish {
+    fn new(key: &Key<Self>) -> Self {
+        Self::new_from_slice(key).expect("fixed-size key is always valid")
+    }
+
+    fn new_from_slice(key: &[u8]) -> Result<Self, InvalidLength> {
+        Blowfish::new(key).map_err(|_| InvalidLength)
+    }
+}
+
+struct EncBackend<'a>(&'a Blowfish);
+
+impl BlockSizeUser for EncBackend<'_> {
+    type BlockSize = U8;
+}
+
+impl ParBlocksSizeUser for EncBackend<'_> {
+    type ParBlocksSize = U1;
+}
+
+impl BlockCipherEncBackend for EncBackend<'_> {
+    fn encrypt_block(&self, mut block: InOut<'_, '_, Block<Self>>) {
+        let mut tmp: [u8; 8] = block.clone_in().into();
+        self.0.encrypt_block(&mut tmp);
+        *block.get_out() = tmp.into();
+    }
+}
+
+impl BlockCipherEncrypt for Blowfish {
+    fn encrypt_with_backend(&self, f: impl BlockCipherEncClosure<BlockSize = U8>) {
+        f.call(&EncBackend(self));
+    }
+}
+
+struct DecBackend<'a>(&'a Blowfish);
+
+impl BlockSizeUser for DecBackend<'_> {
+    type BlockSize = U8;
+}
+
+impl ParBlocksSizeUser for DecBackend<'_> {
+    type ParBlocksSize = U1;
+}
+
+impl BlockCipherDecBackend for DecBackend<'_> {
+    fn decrypt_block(&self, mut block: InOut<'_, '_, Block<Self>>) {
+        let mut tmp: [u8; 8] = block.clone_in().into();
+        self.0.decrypt_block(&mut tmp);
+        *block.get_out() = tmp.into();
+    }
+}
+
+impl BlockCipherDecrypt for Blowfish {
+    fn decrypt_with_backend(&self, f: impl BlockCipherDecClosure<BlockSize = U8>) {
+        f.call(&DecBackend(self));
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const KEY: [u8; 16] = [
+        0x00, 0x11, 0x22, 0x33, 0x44, 0x55, 0x66, 0x77, 0x88, 0x99, 0xaa, 0xbb, 0xcc, 0xdd, 0xee,
+        0xff,
+    ];
+
+    #[test]
+    fn test_cipher_trait_matches_inherent() {
+        let bf = <Blowfish as KeyInit>::new_from_slice(&KEY).unwrap();
+        let plain = [0x65, 0x18, 0xa1, 0xf5, 0xc8, 0xd9, 0xb6, 0x3c];
+
+        // The trait block matches the known single-block vector...
+        let mut block = Block::<Blowfish>::try_from(plain.as_slice()).unwrap();
+        BlockCipherEncrypt::encrypt_block(&bf, &mut block);
+        assert_eq!(block.as_slice(), &[0xda, 0xc6, 0x36, 0x86, 0x1d, 0x70, 0xbd, 0x8a]);
+
+        // ...and agrees with the inherent primitive.
+        let mut inherent = plain;
+        bf.encrypt_block(&mut inherent);
+        assert_eq!(block.as_slice(), &inherent);
+
+        BlockCipherDecrypt::decrypt_block(&bf, &mut block);
+        assert_eq!(block.as_slice(), &plain);
+    }
+}